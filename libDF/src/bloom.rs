@@ -0,0 +1,108 @@
+//! A classic Bloom filter, used for cheap approximate per-epoch membership checks (e.g.
+//! "has this speech/noise/snr combination already been drawn this epoch?") in a few MB
+//! even for corpora with tens of millions of samples.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+pub struct BloomFilter {
+    bits: Vec<AtomicU64>,
+    /// Number of bits in the filter.
+    m: usize,
+    /// Number of hash functions.
+    k: usize,
+}
+
+impl BloomFilter {
+    /// Size a filter for `expected_n` inserts at target false-positive rate `p`, using
+    /// the standard optimal sizing `m = ceil(-n*ln(p) / ln(2)^2)` and
+    /// `k = round((m/n)*ln(2))`.
+    pub fn new(expected_n: usize, false_positive_rate: f64) -> Self {
+        let n = (expected_n.max(1)) as f64;
+        let p = false_positive_rate.clamp(1e-9, 0.5);
+        let m = (-(n * p.ln()) / std::f64::consts::LN_2.powi(2)).ceil().max(64.0) as usize;
+        let k = (((m as f64 / n) * std::f64::consts::LN_2).round().max(1.0)) as usize;
+        let words = m.div_ceil(64);
+        BloomFilter {
+            bits: (0..words).map(|_| AtomicU64::new(0)).collect(),
+            m,
+            k,
+        }
+    }
+
+    /// Derive two independent 64-bit hashes of `key` and combine them via double hashing
+    /// (`h_i = (h1 + i*h2) mod m`) to get the `k` bit positions, avoiding `k` separate
+    /// hash function implementations.
+    fn bit_positions<T: Hash>(&self, key: &T) -> impl Iterator<Item = usize> + '_ {
+        let mut hasher1 = DefaultHasher::new();
+        key.hash(&mut hasher1);
+        let h1 = hasher1.finish();
+
+        let mut hasher2 = DefaultHasher::new();
+        0x9E37_79B9_7F4A_7C15u64.hash(&mut hasher2); // decorrelate from h1
+        key.hash(&mut hasher2);
+        let h2 = hasher2.finish();
+
+        let m = self.m as u64;
+        (0..self.k as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % m) as usize)
+    }
+
+    fn set_bit(&self, pos: usize) {
+        self.bits[pos / 64].fetch_or(1 << (pos % 64), Ordering::Relaxed);
+    }
+
+    fn get_bit(&self, pos: usize) -> bool {
+        self.bits[pos / 64].load(Ordering::Relaxed) & (1 << (pos % 64)) != 0
+    }
+
+    pub fn insert<T: Hash>(&self, key: &T) {
+        for pos in self.bit_positions(key) {
+            self.set_bit(pos);
+        }
+    }
+
+    pub fn contains<T: Hash>(&self, key: &T) -> bool {
+        self.bit_positions(key).all(|pos| self.get_bit(pos))
+    }
+
+    /// Insert `key` and return whether it was new (not already a member). Used to
+    /// dedup a stream of keys in one pass.
+    pub fn insert_if_absent<T: Hash>(&self, key: &T) -> bool {
+        if self.contains(key) {
+            false
+        } else {
+            self.insert(key);
+            true
+        }
+    }
+
+    /// Fraction of bits set, so undersized filters (too many false positives) can be
+    /// detected by users.
+    pub fn fill_ratio(&self) -> f64 {
+        let set: u64 = self.bits.iter().map(|w| w.load(Ordering::Relaxed).count_ones() as u64).sum();
+        set as f64 / self.m as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_if_absent_is_true_once_then_false_for_the_same_key() {
+        let filter = BloomFilter::new(1_000, 0.01);
+        assert!(filter.insert_if_absent(&(1usize, 2usize, 3i8)));
+        assert!(!filter.insert_if_absent(&(1usize, 2usize, 3i8)));
+        assert!(!filter.insert_if_absent(&(1usize, 2usize, 3i8)));
+    }
+
+    #[test]
+    fn distinct_keys_rarely_collide_at_low_fill() {
+        let filter = BloomFilter::new(1_000, 0.01);
+        for i in 0..500usize {
+            assert!(filter.insert_if_absent(&(i, i + 1, i as i8)));
+        }
+        assert!(filter.fill_ratio() < 0.9);
+    }
+}
@@ -0,0 +1,108 @@
+//! Recycling of the flat buffers backing batch tensors.
+//!
+//! `unpack_pad` used to allocate a fresh zero-filled [`ndarray::ArrayD`] for every sample
+//! and batch, which thrashes the allocator for large complex spectrogram buffers across
+//! thousands of batches per epoch. [`BufferPool`] keeps a small set of recycled `Vec<T>`
+//! allocations around, bucketed by the exact element count requested, so a batch of the
+//! same shape as a previously dropped one can reuse its storage instead of allocating.
+//!
+//! Backed by `crossbeam_queue::SegQueue` rather than a hand-rolled Treiber stack: a plain
+//! atomic-CAS stack's `pop` has to dereference `head` before the CAS that unlinks it wins,
+//! which races a concurrent popper freeing that same node without a hazard-pointer or
+//! epoch scheme on top. `SegQueue` already solves that internally, so each bucket's queue
+//! is lock-free without re-deriving memory reclamation by hand. Finding the bucket for a
+//! length takes a short `Mutex` lock on the bucket map on every `checkout` and `recycle`,
+//! so the pool as a whole is not lock-free.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crossbeam_queue::SegQueue;
+use num_traits::Zero;
+
+/// Recycles `Vec<T>` allocations across batches, bucketed by element count.
+///
+/// `checkout` hands out a zero-filled buffer of exactly `len` elements, reusing a
+/// recycled one of the same bucket if available. `recycle` returns a buffer for later
+/// reuse, but drops it instead once a bucket already holds `max_per_bucket` buffers so
+/// retained memory does not grow unbounded when sample lengths vary wildly.
+pub struct BufferPool<T> {
+    buckets: Mutex<HashMap<usize, Arc<SegQueue<Vec<T>>>>>,
+    max_per_bucket: usize,
+}
+
+impl<T> BufferPool<T>
+where
+    T: Clone + Zero + Send + 'static,
+{
+    pub fn new(max_per_bucket: usize) -> Self {
+        BufferPool {
+            buckets: Mutex::new(HashMap::new()),
+            max_per_bucket: max_per_bucket.max(1),
+        }
+    }
+
+    fn bucket(&self, len: usize) -> Arc<SegQueue<Vec<T>>> {
+        self.buckets
+            .lock()
+            .unwrap()
+            .entry(len)
+            .or_insert_with(|| Arc::new(SegQueue::new()))
+            .clone()
+    }
+
+    /// Check out a zero-filled buffer with exactly `len` elements.
+    pub fn checkout(&self, len: usize) -> Vec<T> {
+        match self.bucket(len).pop() {
+            Some(mut buf) => {
+                // A reused buffer may still hold a previous sample's data; stale complex
+                // values would corrupt the padding region if not cleared here.
+                buf.iter_mut().for_each(|x| *x = T::zero());
+                buf
+            }
+            None => vec![T::zero(); len],
+        }
+    }
+
+    /// Return a buffer to the pool for later reuse.
+    pub fn recycle(&self, buf: Vec<T>) {
+        let bucket = self.bucket(buf.len());
+        if bucket.len() < self.max_per_bucket {
+            bucket.push(buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checkout_reuses_recycled_buffer_of_the_same_len() {
+        let pool: BufferPool<f32> = BufferPool::new(4);
+        let mut buf = pool.checkout(16);
+        buf.iter_mut()
+            .enumerate()
+            .for_each(|(i, x)| *x = i as f32 + 1.0);
+        pool.recycle(buf);
+        let reused = pool.checkout(16);
+        assert_eq!(reused.len(), 16);
+        assert!(
+            reused.iter().all(|&x| x == 0.0),
+            "recycled buffer must be re-zeroed"
+        );
+    }
+
+    #[test]
+    fn recycle_drops_buffers_past_max_per_bucket() {
+        let pool: BufferPool<f32> = BufferPool::new(2);
+        // Check all five out before recycling any, so five distinct buffers are
+        // offered back to a bucket capped at two.
+        let bufs: Vec<Vec<f32>> = (0..5).map(|_| pool.checkout(8)).collect();
+        for buf in bufs {
+            pool.recycle(buf);
+        }
+        let bucket = pool.bucket(8);
+        assert_eq!(bucket.len(), 2);
+    }
+}
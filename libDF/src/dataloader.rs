@@ -1,20 +1,32 @@
 use std::collections::BTreeMap;
 use std::collections::VecDeque;
 use std::fmt;
-use std::sync::mpsc::{sync_channel, Receiver};
+use std::future::Future;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
 use std::thread;
-use std::time::Duration;
 
+use async_channel::{bounded, Receiver as AsyncReceiver};
 use crossbeam_channel::unbounded;
+use futures::executor::block_on;
+use futures::stream::Stream;
+use futures::StreamExt;
 use ndarray::prelude::*;
-use rand::prelude::SliceRandom;
+use num_traits::Zero;
 use rayon;
 use rayon::{current_num_threads, prelude::*};
 use thiserror::Error;
 
 type Result<T> = std::result::Result<T, DfDataloaderError>;
 
+use crate::bloom::BloomFilter;
+use crate::buffer_pool::BufferPool;
+use crate::importance::ImportanceSampler;
+use crate::rng::DfRng;
+use crate::tdigest::TDigest;
 use crate::{augmentations::*, dataset::*, util::*, Complex32};
 
 #[derive(Error, Debug)]
@@ -49,10 +61,45 @@ pub enum DfDataloaderError {
     DatasetError(#[from] crate::dataset::DfDatasetError),
     #[error("Ndarray Shape Error")]
     NdarrayShapeError(#[from] ndarray::ShapeError),
+    #[error("Unknown Collate Mode: '{0}'. Expected one of 'zero', 'repeat', 'reflect', 'truncate'.")]
+    UnknownCollateMode(String),
+    #[error(
+        "dedup_fp_rate and importance_sampling cannot be combined: importance sampling intentionally draws the same sample more than once per epoch, which the dedup Bloom filter would then silently drop as a duplicate."
+    )]
+    DedupIncompatibleWithImportanceSampling,
+}
+
+/// How a batch of variable-length samples is brought to a common length along the time
+/// axis before being stacked into a [`DsBatch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum CollateMode {
+    /// Pad the tail of shorter samples with zeros up to the batch target length.
+    #[default]
+    ZeroPad,
+    /// Pad the tail by tiling the sample from its start (wrap-around repeat).
+    RepeatPad,
+    /// Pad the tail by mirroring the existing frames back from the end.
+    ReflectPad,
+    /// Cut every sample down to the shortest sample's length instead of padding.
+    Truncate,
+}
+
+impl FromStr for CollateMode {
+    type Err = DfDataloaderError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "zero" | "zeropad" => Ok(CollateMode::ZeroPad),
+            "repeat" | "repeatpad" => Ok(CollateMode::RepeatPad),
+            "reflect" | "reflectpad" => Ok(CollateMode::ReflectPad),
+            "truncate" => Ok(CollateMode::Truncate),
+            other => Err(DfDataloaderError::UnknownCollateMode(other.to_string())),
+        }
+    }
 }
 
-impl<T> From<std::sync::mpsc::SendError<T>> for DfDataloaderError {
-    fn from(error: std::sync::mpsc::SendError<T>) -> Self {
+impl<T> From<async_channel::SendError<T>> for DfDataloaderError {
+    fn from(error: async_channel::SendError<T>) -> Self {
         DfDataloaderError::SendError(error.to_string())
     }
 }
@@ -68,12 +115,33 @@ pub struct DataLoader {
     idcs: Arc<Mutex<VecDeque<(usize, isize)>>>,
     current_split: Split,
     fill_thread: Option<thread::JoinHandle<Result<()>>>,
-    out_receiver: Option<Receiver<(usize, Result<Sample<Complex32>>)>>,
-    out_buf: BTreeMap<usize, Sample<Complex32>>,
+    out_receiver: Option<AsyncReceiver<(usize, usize, Result<Sample<Complex32>>)>>,
+    out_buf: BTreeMap<usize, (usize, Sample<Complex32>)>,
     cur_out_idx: usize,
     drop_last: bool,
     drained: bool,
     overfit: bool,
+    buf_pool: Arc<BufferPool<Complex32>>,
+    bucket_by_length: Option<usize>,
+    // Precomputed `(start, end)` ordering-index spans of each batch of the current
+    // epoch; contiguous stride-`batch_size` spans unless `bucket_by_length` is set.
+    batch_spans: Vec<(usize, usize)>,
+    // Per-batch maximum sample frame length, parallel to `batch_spans`: the collate
+    // target length for that batch, so a length-bucketed batch only pads up to its own
+    // members' longest sample instead of the whole dataset's.
+    batch_max_lens: Vec<usize>,
+    collate_mode: CollateMode,
+    dedup_fp_rate: Option<f64>,
+    dedup_expected_n: Option<usize>,
+    // Recreated fresh at the start of every epoch so stale membership never leaks
+    // across epochs.
+    dedup: Option<Arc<BloomFilter>>,
+    // Persists across epochs (unlike `dedup`): running per-sample loss priorities are
+    // meant to accumulate over the course of training.
+    importance: Option<ImportanceSampler>,
+    // Persists across epochs, like `importance`: a corpus-wide SNR/gain/loudness/length
+    // profile is only useful accumulated over many epochs, not reset each one.
+    stats: Option<DatasetStats>,
 }
 
 #[derive(Default)]
@@ -85,6 +153,12 @@ pub struct DataLoaderBuilder {
     _num_threads: Option<usize>,
     _drop_last: Option<bool>,
     _overfit: Option<bool>,
+    _bucket_by_length: Option<usize>,
+    _collate_mode: Option<CollateMode>,
+    _dedup_fp_rate: Option<f64>,
+    _dedup_expected_n: Option<usize>,
+    _importance: Option<(f64, f64)>,
+    _track_stats: bool,
 }
 
 impl DataLoaderBuilder {
@@ -97,6 +171,12 @@ impl DataLoaderBuilder {
             _num_threads: None,
             _drop_last: None,
             _overfit: None,
+            _bucket_by_length: None,
+            _collate_mode: None,
+            _dedup_fp_rate: None,
+            _dedup_expected_n: None,
+            _importance: None,
+            _track_stats: false,
         }
     }
     pub fn batch_size(mut self, batch_size: usize) -> Self {
@@ -123,6 +203,57 @@ impl DataLoaderBuilder {
         self._drop_last = Some(drop_last);
         self
     }
+    /// Partition each epoch's shuffled sample indices into `n_buckets` contiguous
+    /// length buckets (by per-sample frame length) and form batches within each
+    /// bucket, so members of a batch have similar length and padding waste stays
+    /// low. The order of the resulting batches is still shuffled to retain
+    /// stochasticity; only the sample-to-batch assignment is length-aware.
+    pub fn bucket_by_length(mut self, n_buckets: usize) -> Self {
+        self._bucket_by_length = Some(n_buckets);
+        self
+    }
+    /// Set how samples are brought to a common length within a batch. Defaults to
+    /// [`CollateMode::ZeroPad`]. Parse from a config string with `mode.parse()?`.
+    pub fn collate_mode(mut self, mode: CollateMode) -> Self {
+        self._collate_mode = Some(mode);
+        self
+    }
+    /// Skip samples whose composite `(speech, noise/snr)` draw key has already been
+    /// emitted this epoch, approximated via a [`BloomFilter`] reset at the start of
+    /// every epoch at the target false-positive rate `fp_rate`. Biases the epoch
+    /// towards fewer repeated speech/noise/SNR combinations when mixing introduces
+    /// randomness beyond the shuffle order.
+    pub fn dedup_fp_rate(mut self, fp_rate: f64) -> Self {
+        self._dedup_fp_rate = Some(fp_rate);
+        self
+    }
+    /// Override the expected per-epoch sample count used to size the dedup
+    /// [`BloomFilter`]. Defaults to the split's sample count; set this explicitly if
+    /// the realized number of distinct draws differs, e.g. because of a custom
+    /// dataset sampling factor.
+    pub fn dedup_expected_n(mut self, expected_n: usize) -> Self {
+        self._dedup_expected_n = Some(expected_n);
+        self
+    }
+    /// Bias future epochs towards samples the training loop reports high losses for
+    /// via [`DataLoader::push_loss`]. `gamma` decays each sample's running loss
+    /// before adding the next report (recent losses matter more); `alpha` blends
+    /// priority-weighted draws with plain uniform draws (`0.0` draws uniformly, `1.0`
+    /// draws by priority); with either setting some draws are still forced to
+    /// never-scored ids. See [`ImportanceSampler`].
+    pub fn importance_sampling(mut self, gamma: f64, alpha: f64) -> Self {
+        self._importance = Some((gamma, alpha));
+        self
+    }
+    /// Accumulate a corpus-wide [`DatasetStats`] profile (SNR, gain, loudness, frame
+    /// length) as batches are produced, queryable via [`DataLoader::stats`]. Adds a
+    /// per-sample RMS-magnitude pass over each batch's speech spectrogram, so only
+    /// enable this when the profile is actually needed (e.g. a one-off corpus report),
+    /// not on every training run.
+    pub fn track_stats(mut self) -> Self {
+        self._track_stats = true;
+        self
+    }
     fn check_dataset_size(&self, bs_train: usize) -> Result<()> {
         for split in [Split::Train, Split::Valid, Split::Test] {
             let batch_size = match split {
@@ -141,9 +272,14 @@ impl DataLoaderBuilder {
         Ok(())
     }
     pub fn build(self) -> Result<DataLoader> {
+        if self._dedup_fp_rate.is_some() && self._importance.is_some() {
+            return Err(DfDataloaderError::DedupIncompatibleWithImportanceSampling);
+        }
         let bs_train = self._batch_size.unwrap_or(1);
         self.check_dataset_size(bs_train)?;
         let prefetch = self._prefetch.unwrap_or(bs_train * self._num_threads.unwrap_or(4) * 2);
+        let train_len = self._ds.as_ref().unwrap().get(Split::Train).len();
+        let importance = self._importance;
         let mut loader = DataLoader::new(
             self._ds.unwrap(),
             bs_train,
@@ -153,6 +289,12 @@ impl DataLoaderBuilder {
             self._drop_last.unwrap_or(false),
         )?;
         loader.overfit = self._overfit.unwrap_or(false);
+        loader.bucket_by_length = self._bucket_by_length;
+        loader.collate_mode = self._collate_mode.unwrap_or_default();
+        loader.dedup_fp_rate = self._dedup_fp_rate;
+        loader.dedup_expected_n = self._dedup_expected_n;
+        loader.importance = importance.map(|(gamma, alpha)| ImportanceSampler::new(train_len, gamma, alpha));
+        loader.stats = self._track_stats.then(DatasetStats::new);
         Ok(loader)
     }
 }
@@ -186,6 +328,9 @@ impl DataLoader {
             }
         };
         let batch_size_eval = batch_size_eval.unwrap_or(batch_size_train);
+        // Cap retained buffers per length bucket so memory does not grow unbounded when
+        // sample lengths vary wildly across an epoch.
+        let buf_pool = Arc::new(BufferPool::new(num_prefech * batch_size_train.max(1)));
         Ok(DataLoader {
             ds_train: Some(Arc::new(datasets.train)),
             ds_valid: Some(Arc::new(datasets.valid)),
@@ -203,6 +348,16 @@ impl DataLoader {
             drop_last,
             drained: false,
             overfit: false,
+            buf_pool,
+            bucket_by_length: None,
+            batch_spans: Vec::new(),
+            batch_max_lens: Vec::new(),
+            collate_mode: CollateMode::default(),
+            dedup_fp_rate: None,
+            dedup_expected_n: None,
+            dedup: None,
+            importance: None,
+            stats: None,
         })
     }
 
@@ -233,9 +388,26 @@ impl DataLoader {
         len
     }
 
+    /// Number of batches the current (or next) epoch will actually emit.
+    ///
+    /// Once `start_epoch` has populated `batch_spans` for this `split`, count those
+    /// spans directly rather than assuming fixed-stride `dataset_len / batch_size`:
+    /// `bucket_by_length` hands out one (possibly short) tail span per bucket, so the
+    /// fixed-stride formula can be off by up to `n_buckets - 1` batches relative to what
+    /// `BatchStream` actually emits (too high with `drop_last`, too low without). Falls back to the fixed-stride estimate
+    /// before the first `start_epoch` call, when `batch_spans` is still empty.
     pub fn dataloader_len<S: Into<Split> + Copy>(&self, split: S) -> usize {
         let bs = self.batch_size(split);
-        if self.drop_last {
+        if split.into() == self.current_split && !self.batch_spans.is_empty() {
+            if self.drop_last {
+                self.batch_spans
+                    .iter()
+                    .filter(|&&(start, end)| end - start >= bs)
+                    .count()
+            } else {
+                self.batch_spans.len()
+            }
+        } else if self.drop_last {
             self.dataset_len(split) / bs
         } else {
             (self.dataset_len(split) as f32 / bs as f32).ceil() as usize
@@ -263,7 +435,7 @@ impl DataLoader {
                 self.num_prefech, bs
             )
         }
-        let (out_sender, out_receiver) = sync_channel(self.num_prefech);
+        let (out_sender, out_receiver) = bounded(self.num_prefech);
         self.out_receiver = Some(out_receiver);
         let ds = self.get_ds_arc(split);
         let (in_sender, in_receiver) = unbounded();
@@ -274,15 +446,22 @@ impl DataLoader {
 
         let worker_recievers: Vec<_> = (0..self.num_workers).map(|_| in_receiver.clone()).collect();
         let handle = thread::spawn(move || -> Result<()> {
+            // Every worker seeds `get_sample` from the same `epoch_seed`, not a
+            // per-worker `DfRng::jump()` substream: workers race a shared, unordered
+            // `in_receiver`, so which worker ends up processing a given `sample_idx` is
+            // scheduling-dependent and varies between runs. Keying the per-sample seed
+            // off worker_id would make it a function of that race instead of a pure
+            // function of `(epoch_seed, sample_idx)`, breaking the run-to-run
+            // reproducibility this is meant to provide.
             worker_recievers.par_iter().try_for_each(|r| {
                 while let Ok((sample_idx, ordering_idx)) = r.recv() {
                     if ordering_idx == -1 {
-                        out_sender.send((0, Err(DfDataloaderError::DatasetDrained)))?;
+                        out_sender.send_blocking((0, sample_idx, Err(DfDataloaderError::DatasetDrained)))?;
                         return Ok(());
                     }
                     assert!(ordering_idx >= 0);
                     let sample = ds.get_sample(sample_idx, Some(epoch_seed));
-                    out_sender.send((ordering_idx as usize, sample.map_err(|e| e.into())))?;
+                    out_sender.send_blocking((ordering_idx as usize, sample_idx, sample.map_err(|e| e.into())))?;
                 }
                 Ok(())
             })
@@ -323,21 +502,56 @@ impl DataLoader {
             epoch_seed = 0;
         }
         seed_from_u64(epoch_seed as u64);
+        // Deterministic, splittable source for all shuffling this epoch: the same
+        // `epoch_seed` always produces the same batch order, independent of the
+        // thread-local `rand` state.
+        let mut rng = DfRng::new(epoch_seed as u64);
         {
             // Recreate indices to index into the dataset and shuffle them
             let n_samples = self.dataset_len(split);
-            let sample_idcs: Vec<usize> = if self.overfit {
+            let bs = self.batch_size(split);
+            let (sample_idcs, spans): (Vec<usize>, Vec<(usize, usize)>) = if self.overfit {
                 println!("Overfitting on one batch.");
-                (0..n_samples).cycle().take(n_samples).collect()
+                let idcs: Vec<usize> = (0..n_samples).cycle().take(n_samples).collect();
+                (idcs, Self::fixed_stride_spans(n_samples, bs))
+            } else if split == Split::Train && self.importance.is_some() {
+                // Hard-example mining only makes sense against reported training
+                // losses; valid/test epochs always fall through to plain shuffling.
+                if self.bucket_by_length.is_some() {
+                    eprintln!(
+                        "Warning: bucket_by_length is ignored while importance_sampling is \
+                         enabled; importance-sampled draws are assigned fixed-stride batches."
+                    );
+                }
+                let idcs = self.importance.as_mut().unwrap().sample(n_samples, n_samples, &mut rng);
+                (idcs, Self::fixed_stride_spans(n_samples, bs))
+            } else if let Some(n_buckets) = self.bucket_by_length {
+                self.bucketed_epoch(split, n_samples, bs, n_buckets, &mut rng)?
             } else {
                 let mut tmp = (0..n_samples).collect::<Vec<usize>>();
-                tmp.shuffle(&mut thread_rng()?);
-                tmp
+                rng.shuffle(&mut tmp);
+                let spans = Self::fixed_stride_spans(n_samples, bs);
+                (tmp, spans)
             };
+            // Per-batch collate target length: the longest sample actually assigned to
+            // that batch, not the dataset-wide max, so `bucket_by_length` batches only
+            // pad up to their own members instead of the whole dataset's longest clip.
+            let ds = self.get_ds_arc(split);
+            self.batch_max_lens = spans
+                .iter()
+                .map(|&(start, end)| {
+                    sample_idcs[start..end].iter().map(|&i| ds.sample_len(i)).max().unwrap_or(0)
+                })
+                .collect();
+            self.batch_spans = spans;
             // Concatenate an ordering index
             let idcs: VecDeque<(usize, isize)> =
                 sample_idcs.into_iter().zip(0..self.dataset_len(split) as isize).collect();
             self.idcs.lock().unwrap().clone_from(&idcs);
+            // Fresh per-epoch Bloom filter so membership never leaks across epochs.
+            self.dedup = self.dedup_fp_rate.map(|p| {
+                Arc::new(BloomFilter::new(self.dedup_expected_n.unwrap_or(n_samples), p))
+            });
         }
         // Start thread to submit dataset jobs for the pool workers
         self.fill_thread = Some(self.start_idx_worker(split, epoch_seed as u64)?);
@@ -345,78 +559,151 @@ impl DataLoader {
         Ok(())
     }
 
-    pub fn get_batch<C>(&mut self) -> Result<Option<DsBatch<Complex32>>>
+    /// Contiguous `batch_size`-wide `(start, end)` spans over `0..n_samples`, the
+    /// default batching scheme used when [`DataLoaderBuilder::bucket_by_length`] is
+    /// not set.
+    fn fixed_stride_spans(n_samples: usize, batch_size: usize) -> Vec<(usize, usize)> {
+        let bs = batch_size.max(1);
+        let mut spans = Vec::with_capacity((n_samples + bs - 1) / bs);
+        let mut start = 0;
+        while start < n_samples {
+            let end = (start + bs).min(n_samples);
+            spans.push((start, end));
+            start = end;
+        }
+        spans
+    }
+
+    /// Assign samples to batches so that each batch only contains samples of similar
+    /// frame length, then shuffle the order the batches are emitted in (not the
+    /// samples within a bucket) to retain stochasticity across epochs.
+    fn bucketed_epoch(
+        &self,
+        split: Split,
+        n_samples: usize,
+        batch_size: usize,
+        n_buckets: usize,
+        rng: &mut DfRng,
+    ) -> Result<(Vec<usize>, Vec<(usize, usize)>)> {
+        let ds = self.get_ds_arc(split);
+        let mut idcs: Vec<usize> = (0..n_samples).collect();
+        rng.shuffle(&mut idcs);
+        // Stable sort: samples of equal length keep their shuffled relative order, so
+        // bucket membership is still randomized across epochs.
+        idcs.sort_by_key(|&i| ds.sample_len(i));
+
+        let n_buckets = n_buckets.max(1);
+        let bucket_len = ((n_samples + n_buckets - 1) / n_buckets).max(1);
+        let mut batches: Vec<Vec<usize>> = Vec::new();
+        for bucket in idcs.chunks(bucket_len) {
+            for batch in bucket.chunks(batch_size.max(1)) {
+                batches.push(batch.to_vec());
+            }
+        }
+        rng.shuffle(&mut batches);
+
+        let mut sample_idcs = Vec::with_capacity(n_samples);
+        let mut spans = Vec::with_capacity(batches.len());
+        for batch in batches {
+            let start = sample_idcs.len();
+            sample_idcs.extend(batch);
+            spans.push((start, sample_idcs.len()));
+        }
+        Ok((sample_idcs, spans))
+    }
+
+    /// Build a [`BatchStream`] over the remainder of the current epoch.
+    ///
+    /// This is the non-blocking counterpart to [`DataLoader::get_batch`]: instead of
+    /// busy-polling the worker channel with a timeout, it hands out an
+    /// `impl futures::Stream` that can be `.await`ed from an async training loop. The
+    /// stream borrows `self` for as long as it lives and reassembles batches directly
+    /// into `self.out_buf`/`self.cur_out_idx`/`self.drained`, so the borrow checker —
+    /// not a runtime check — rules out calling `batch_stream` or `get_batch` again on
+    /// the same loader before this stream is dropped or exhausted; there is no stale
+    /// state left behind to reconcile either way.
+    pub fn batch_stream<C>(&mut self) -> BatchStream<'_, C>
     where
         C: Collate<Complex32>,
     {
+        let total_len = self.dataset_len(self.current_split);
         let bs = self.batch_size(self.current_split);
-        let mut samples = Vec::with_capacity(bs);
-        let target_idx = self.dataset_len(self.current_split).min(self.cur_out_idx + bs);
-        if self.cur_out_idx >= self.dataset_len(self.current_split) {
+        if self.cur_out_idx >= total_len {
             self.drained = true;
         }
-        let mut tries = 0;
-        let mut ids = Vec::with_capacity(self.batch_size(self.current_split));
-        let reciever = match self.out_receiver.as_ref() {
-            None => {
-                return Err(DfDataloaderError::ChannelsNotInitializedError);
-            }
-            Some(r) => r,
-        };
-        'outer: while self.cur_out_idx < target_idx {
-            // Check if we have some buffered samples
-            if let Some(s) = self.out_buf.remove(&self.cur_out_idx) {
-                ids.push(self.cur_out_idx);
-                samples.push(s);
-                self.cur_out_idx += 1;
-            } else {
-                // Or check worker threads
-                match reciever.recv_timeout(Duration::from_millis(100)) {
-                    Err(_e) => {
-                        if tries > 1000 {
-                            return Err(DfDataloaderError::TimeoutError);
-                        }
-                        tries += 1;
-                        continue 'outer;
-                    }
-                    Ok((_, Err(DfDataloaderError::DatasetDrained))) => {
-                        self.drained = true;
-                    }
-                    Ok((_, Err(e))) => {
-                        return Err(e);
-                    }
-                    Ok((o_idx, Ok(s))) => {
-                        if o_idx == self.cur_out_idx {
-                            samples.push(s);
-                            ids.push(o_idx);
-                            self.cur_out_idx += 1;
-                        } else {
-                            assert!(self.out_buf.insert(o_idx, s).is_none());
-                        }
-                    }
-                }
-            }
-            tries = 0;
+        let fallback_max_len = self.get_ds_arc(self.current_split).max_sample_len();
+        let (target_idx, max_sample_len) = span_info(
+            &self.batch_spans,
+            &self.batch_max_lens,
+            self.cur_out_idx,
+            total_len,
+            fallback_max_len,
+        );
+        let receiver = self.out_receiver.clone();
+        let spans = self.batch_spans.clone();
+        let span_max_lens = self.batch_max_lens.clone();
+        let buf_pool = self.buf_pool.clone();
+        let collate_mode = self.collate_mode;
+        let dedup = self.dedup.clone();
+        let drop_last = self.drop_last;
+        BatchStream {
+            loader: self,
+            receiver,
+            target_idx,
+            total_len,
+            batch_size: bs,
+            spans,
+            span_max_lens,
+            max_sample_len,
+            fallback_max_len,
+            drop_last,
+            ids: Vec::with_capacity(bs),
+            sample_ids: Vec::with_capacity(bs),
+            samples: Vec::with_capacity(bs),
+            recv_fut: None,
+            buf_pool,
+            collate_mode,
+            dedup,
+            _collate: PhantomData,
         }
+    }
 
-        let out = if self.drained && (self.drop_last || samples.is_empty()) {
-            assert!(self.cur_out_idx >= target_idx);
-            assert!(self.out_buf.is_empty());
-            self.join_fill_thread()?;
-            None
-        } else {
-            let mut batch = C::collate(
-                samples.as_mut_slice(),
-                self.get_ds_arc(self.current_split).max_sample_len(),
-            )?;
-            batch.ids.extend(ids);
-            debug_assert!(batch.batch_size() <= self.batch_size(self.current_split));
-            if !self.drained && self.cur_out_idx < target_idx {
-                debug_assert_eq!(batch.batch_size(), self.batch_size(self.current_split));
-            }
-            Some(batch)
-        };
-        Ok(out)
+    /// Blocking convenience wrapper around [`DataLoader::batch_stream`] so existing
+    /// synchronous callers are unaffected by the switch to an async-aware channel.
+    pub fn get_batch<C>(&mut self) -> Result<Option<DsBatch<Complex32>>>
+    where
+        C: Collate<Complex32>,
+    {
+        if self.out_receiver.is_none() {
+            return Err(DfDataloaderError::ChannelsNotInitializedError);
+        }
+        let mut stream = self.batch_stream::<C>();
+        block_on(stream.next()).transpose()
+    }
+
+    /// Fraction of bits set in the current epoch's dedup [`BloomFilter`], or `None` if
+    /// [`DataLoaderBuilder::dedup_fp_rate`] was not configured. A ratio approaching 1.0
+    /// means the filter was undersized for the epoch and false positives (spurious
+    /// skips) are likely.
+    pub fn dedup_fill_ratio(&self) -> Option<f64> {
+        self.dedup.as_ref().map(|f| f.fill_ratio())
+    }
+
+    /// Feed back a per-sample loss from the training loop so the next epoch's draws
+    /// (if [`DataLoaderBuilder::importance_sampling`] is enabled) bias towards `id`
+    /// proportionally. A no-op if importance sampling was not enabled. `id` is the
+    /// dataset sample index, as returned in [`DsBatch::sample_ids`].
+    pub fn push_loss(&mut self, id: usize, loss: f32) {
+        if let Some(sampler) = self.importance.as_mut() {
+            sampler.push_loss(id, loss as f64);
+        }
+    }
+
+    /// The running [`DatasetStats`] profile, if [`DataLoaderBuilder::track_stats`] was
+    /// enabled; updated automatically as batches are produced by [`DataLoader::get_batch`]
+    /// or [`DataLoader::batch_stream`].
+    pub fn stats(&self) -> Option<&DatasetStats> {
+        self.stats.as_ref()
     }
 
     pub fn join_fill_thread(&mut self) -> Result<()> {
@@ -441,15 +728,236 @@ impl DataLoader {
     }
 }
 
+/// Look up the `(end, collate target length)` of the batch span starting at
+/// `cur_out_idx`, falling back to `(total_len, fallback_max_len)` if `spans` is empty
+/// (e.g. the epoch has not been started yet).
+fn span_info(
+    spans: &[(usize, usize)],
+    max_lens: &[usize],
+    cur_out_idx: usize,
+    total_len: usize,
+    fallback_max_len: usize,
+) -> (usize, usize) {
+    match spans.binary_search_by_key(&cur_out_idx, |&(s, _)| s) {
+        Ok(i) => (spans[i].1, max_lens[i]),
+        Err(_) => (total_len, fallback_max_len),
+    }
+}
+
+/// A [`Stream`] of reordered, collated batches drained from the worker output channel.
+///
+/// Samples arrive out of order from the rayon worker pool, so the stream buffers
+/// `(ordering_idx, sample)` pairs in `loader.out_buf` until the next contiguous index
+/// is available, mirroring the reordering logic previously inlined in `get_batch`.
+/// Borrowing `loader` for `'a` (rather than taking a snapshot of its reassembly state
+/// and handing it back on completion) means the reassembly buffer, draw cursor and
+/// drained flag live on the loader the whole time: there is nothing to reconcile once
+/// the stream is dropped, and the borrow checker rejects any attempt to call
+/// `batch_stream`/`get_batch` again on the same loader while this stream is still alive.
+pub struct BatchStream<'a, C> {
+    loader: &'a mut DataLoader,
+    receiver: Option<AsyncReceiver<(usize, usize, Result<Sample<Complex32>>)>>,
+    target_idx: usize,
+    total_len: usize,
+    batch_size: usize,
+    spans: Vec<(usize, usize)>,
+    // Per-span collate target length, parallel to `spans`.
+    span_max_lens: Vec<usize>,
+    // Collate target length of the batch currently being assembled; refreshed from
+    // `span_max_lens` every time `target_idx` advances to a new span.
+    max_sample_len: usize,
+    // Target length to fall back to if `cur_out_idx` does not land on a known span,
+    // i.e. the whole-dataset max sample length.
+    fallback_max_len: usize,
+    drop_last: bool,
+    ids: Vec<usize>,
+    sample_ids: Vec<usize>,
+    samples: Vec<Sample<Complex32>>,
+    buf_pool: Arc<BufferPool<Complex32>>,
+    collate_mode: CollateMode,
+    // Reset once per epoch by [`DataLoader::start_epoch`]; `None` when dedup is
+    // disabled.
+    dedup: Option<Arc<BloomFilter>>,
+    #[allow(clippy::type_complexity)]
+    recv_fut: Option<
+        Pin<Box<dyn Future<Output = std::result::Result<(usize, usize, Result<Sample<Complex32>>), async_channel::RecvError>> + Send>>,
+    >,
+    _collate: PhantomData<C>,
+}
+
+/// Composite per-sample dedup key: the underlying speech/noise clip identity together
+/// with the realized SNR, which together identify the actual (speech, noise, snr) mix
+/// produced for this sample. Deliberately *not* the epoch-local shuffle draw index:
+/// that index is drawn from a Fisher-Yates permutation of `0..n_samples` (or, under
+/// importance sampling, with replacement from a smaller id set), so keying on it would
+/// either never collide at all or collide on draw order rather than on the clip
+/// combination the request actually asks to dedup.
+fn dedup_key(sample: &Sample<Complex32>) -> (usize, usize, i8) {
+    (sample.speech_id, sample.noise_id, sample.snr)
+}
+
+impl<'a, C> BatchStream<'a, C> {
+    /// Check-and-insert `sample`'s dedup key; `true` if it was already seen this
+    /// epoch (so it should be skipped). Always `false` when dedup is disabled.
+    fn is_dup(&self, sample: &Sample<Complex32>) -> bool {
+        match &self.dedup {
+            Some(filter) => !filter.insert_if_absent(&dedup_key(sample)),
+            None => false,
+        }
+    }
+}
+
+impl<'a, C> Stream for BatchStream<'a, C>
+where
+    C: Collate<Complex32> + Unpin,
+{
+    type Item = Result<DsBatch<Complex32>>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        // Assemble spans one at a time; a short span (fewer than `batch_size` samples,
+        // which with length-bucketed batching can happen at the tail of any bucket, not
+        // just the end of the epoch) is discarded and skipped when `drop_last` is set.
+        'spans: loop {
+            if this.loader.cur_out_idx >= this.target_idx {
+                if this.loader.cur_out_idx >= this.total_len {
+                    this.loader.drained = true;
+                    return Poll::Ready(None);
+                }
+                let (target_idx, max_sample_len) = span_info(
+                    &this.spans,
+                    &this.span_max_lens,
+                    this.loader.cur_out_idx,
+                    this.total_len,
+                    this.fallback_max_len,
+                );
+                this.target_idx = target_idx;
+                this.max_sample_len = max_sample_len;
+            }
+            while this.loader.cur_out_idx < this.target_idx {
+                if let Some((sample_idx, s)) =
+                    this.loader.out_buf.remove(&this.loader.cur_out_idx)
+                {
+                    let o_idx = this.loader.cur_out_idx;
+                    this.loader.cur_out_idx += 1;
+                    // A duplicate just advances past the slot; the batch ends up one
+                    // sample short, same as the drop_last short-span path below.
+                    if !this.is_dup(&s) {
+                        this.ids.push(o_idx);
+                        this.sample_ids.push(sample_idx);
+                        this.samples.push(s);
+                    }
+                    continue;
+                }
+                if this.recv_fut.is_none() {
+                    let receiver = match this.receiver.clone() {
+                        Some(r) => r,
+                        None => {
+                            this.loader.drained = true;
+                            break;
+                        }
+                    };
+                    this.recv_fut = Some(Box::pin(async move { receiver.recv().await }));
+                }
+                match this.recv_fut.as_mut().unwrap().as_mut().poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(Err(_closed)) => {
+                        this.recv_fut = None;
+                        this.loader.drained = true;
+                        break;
+                    }
+                    Poll::Ready(Ok((_, _, Err(DfDataloaderError::DatasetDrained)))) => {
+                        this.recv_fut = None;
+                        this.loader.drained = true;
+                    }
+                    Poll::Ready(Ok((_, _, Err(e)))) => {
+                        this.recv_fut = None;
+                        return Poll::Ready(Some(Err(e)));
+                    }
+                    Poll::Ready(Ok((o_idx, sample_idx, Ok(s)))) => {
+                        this.recv_fut = None;
+                        if o_idx == this.loader.cur_out_idx {
+                            this.loader.cur_out_idx += 1;
+                            if !this.is_dup(&s) {
+                                this.ids.push(o_idx);
+                                this.sample_ids.push(sample_idx);
+                                this.samples.push(s);
+                            }
+                        } else {
+                            assert!(this.loader.out_buf.insert(o_idx, (sample_idx, s)).is_none());
+                        }
+                    }
+                }
+            }
+
+            if this.samples.is_empty() {
+                // Every slot of this span was skipped as a dedup duplicate: go on to the
+                // next span instead of emitting an empty batch.
+                if this.loader.cur_out_idx >= this.target_idx {
+                    continue 'spans;
+                }
+                assert!(this.loader.drained);
+                assert!(this.loader.out_buf.is_empty());
+                return Poll::Ready(None);
+            }
+            if this.drop_last && this.samples.len() < this.batch_size {
+                this.samples.clear();
+                this.ids.clear();
+                this.sample_ids.clear();
+                if this.loader.drained {
+                    return Poll::Ready(None);
+                }
+                continue 'spans;
+            }
+            break;
+        }
+
+        let max_sample_len = this.max_sample_len;
+        let mut batch = match C::collate(
+            this.samples.as_mut_slice(),
+            max_sample_len,
+            this.collate_mode,
+            Some(&this.buf_pool),
+        ) {
+            Ok(b) => b,
+            Err(e) => return Poll::Ready(Some(Err(e))),
+        };
+        batch.ids.extend(this.ids.drain(..));
+        batch.sample_ids.extend(this.sample_ids.drain(..));
+        this.samples.clear();
+        debug_assert!(batch.batch_size() <= this.batch_size);
+        if let Some(stats) = this.loader.stats.as_mut() {
+            stats.update(&batch);
+        }
+        Poll::Ready(Some(Ok(batch)))
+    }
+}
+
 pub trait Collate<T: Data> {
-    fn collate(samples: &mut [Sample<T>], len: usize) -> Result<DsBatch<T>>;
+    fn collate(
+        samples: &mut [Sample<T>],
+        len: usize,
+        mode: CollateMode,
+        pool: Option<&Arc<BufferPool<T>>>,
+    ) -> Result<DsBatch<T>>;
 }
 impl Collate<f32> for f32 {
-    fn collate(samples: &mut [Sample<f32>], len: usize) -> Result<DsBatch<f32>> {
-        let lengths = samples.iter().map(|s| s.speech.len_of(Axis(1))).collect();
-        let speech = unpack_pad(|s: &mut Sample<f32>| &mut s.speech, samples, len)?;
-        let noise = unpack_pad(|s: &mut Sample<f32>| &mut s.noise, samples, len)?;
-        let noisy = unpack_pad(|s: &mut Sample<f32>| &mut s.noisy, samples, len)?;
+    fn collate(
+        samples: &mut [Sample<f32>],
+        len: usize,
+        mode: CollateMode,
+        pool: Option<&Arc<BufferPool<f32>>>,
+    ) -> Result<DsBatch<f32>> {
+        let target_len = match mode {
+            CollateMode::Truncate => {
+                samples.iter().map(|s| s.speech.len_of(Axis(1))).min().unwrap_or(len)
+            }
+            _ => len,
+        };
+        let lengths = samples.iter().map(|s| s.speech.len_of(Axis(1)).min(target_len)).collect();
+        let speech = unpack_pad(|s: &mut Sample<f32>| &mut s.speech, samples, target_len, mode, pool)?;
+        let noise = unpack_pad(|s: &mut Sample<f32>| &mut s.noise, samples, target_len, mode, pool)?;
+        let noisy = unpack_pad(|s: &mut Sample<f32>| &mut s.noisy, samples, target_len, mode, pool)?;
         let max_freq = samples.iter().map(|s| s.max_freq).collect();
         let snr = samples.iter().map(|s| s.snr).collect();
         let gain = samples.iter().map(|s| s.gain).collect();
@@ -466,20 +974,36 @@ impl Collate<f32> for f32 {
             gain,
             atten,
             ids: Vec::new(),
+            sample_ids: Vec::new(),
+            buf_pool: pool.cloned(),
+            feat_spec_pool: None,
         })
     }
 }
 impl Collate<Complex32> for Complex32 {
-    fn collate(samples: &mut [Sample<Complex32>], len: usize) -> Result<DsBatch<Complex32>> {
-        let lengths = samples.iter().map(|s| s.speech.len_of(Axis(1))).collect();
-        let speech = unpack_pad(|s: &mut Sample<Complex32>| &mut s.speech, samples, len)?;
-        let noise = unpack_pad(|s: &mut Sample<Complex32>| &mut s.noise, samples, len)?;
-        let noisy = unpack_pad(|s: &mut Sample<Complex32>| &mut s.noisy, samples, len)?;
+    fn collate(
+        samples: &mut [Sample<Complex32>],
+        len: usize,
+        mode: CollateMode,
+        pool: Option<&Arc<BufferPool<Complex32>>>,
+    ) -> Result<DsBatch<Complex32>> {
+        let target_len = match mode {
+            CollateMode::Truncate => {
+                samples.iter().map(|s| s.speech.len_of(Axis(1))).min().unwrap_or(len)
+            }
+            _ => len,
+        };
+        let lengths = samples.iter().map(|s| s.speech.len_of(Axis(1)).min(target_len)).collect();
+        let speech = unpack_pad(|s: &mut Sample<Complex32>| &mut s.speech, samples, target_len, mode, pool)?;
+        let noise = unpack_pad(|s: &mut Sample<Complex32>| &mut s.noise, samples, target_len, mode, pool)?;
+        let noisy = unpack_pad(|s: &mut Sample<Complex32>| &mut s.noisy, samples, target_len, mode, pool)?;
         let feat_erb = if samples.first().unwrap().feat_erb.is_some() {
             Some(unpack_pad(
                 |s: &mut Sample<Complex32>| s.feat_erb.as_mut().unwrap(),
                 samples,
-                len,
+                target_len,
+                mode,
+                None,
             )?)
         } else {
             None
@@ -488,7 +1012,9 @@ impl Collate<Complex32> for Complex32 {
             Some(unpack_pad(
                 |s: &mut Sample<Complex32>| s.feat_spec.as_mut().unwrap(),
                 samples,
-                len,
+                target_len,
+                mode,
+                pool,
             )?)
         } else {
             None
@@ -509,6 +1035,9 @@ impl Collate<Complex32> for Complex32 {
             gain,
             atten,
             ids: Vec::new(),
+            sample_ids: Vec::new(),
+            buf_pool: pool.cloned(),
+            feat_spec_pool: pool.cloned(),
         })
     }
 }
@@ -534,6 +1063,15 @@ where
     pub gain: Vec<i8>,
     pub atten: Vec<u8>, // attenuation limit in dB; 0 stands for no limit
     pub ids: Vec<usize>,
+    /// The underlying dataset sample index of each batch member, stable across
+    /// epochs (unlike `ids`, which is the epoch-local draw order); feed these back
+    /// through [`DataLoader::push_loss`] for importance sampling.
+    pub sample_ids: Vec<usize>,
+    buf_pool: Option<Arc<BufferPool<T>>>,
+    // `feat_spec` is always `Complex32`-valued regardless of the batch's own `T`, so it
+    // is checked out of (and, on `Drop`, returned to) its own pool rather than
+    // `buf_pool`, which is typed for `T`.
+    feat_spec_pool: Option<Arc<BufferPool<Complex32>>>,
 }
 impl<T> DsBatch<T>
 where
@@ -546,6 +1084,32 @@ where
         self.speech.len_of(Axis(2))
     }
 }
+impl<T> Drop for DsBatch<T>
+where
+    T: Data + Zero,
+{
+    fn drop(&mut self) {
+        // Return the main waveform buffers to the pool so the next batch of the same
+        // shape can reuse their allocation instead of hitting the allocator.
+        if let Some(pool) = self.buf_pool.take() {
+            let empty = || ArrayD::<T>::zeros(IxDyn(&[0]));
+            for arr in [
+                std::mem::replace(&mut self.speech, empty()),
+                std::mem::replace(&mut self.noise, empty()),
+                std::mem::replace(&mut self.noisy, empty()),
+            ] {
+                pool.recycle(arr.into_raw_vec());
+            }
+        }
+        // `feat_spec` is checked out of its own pool (see `feat_spec_pool`'s doc
+        // comment), so it is returned here rather than by the loop above.
+        if let Some(pool) = self.feat_spec_pool.take() {
+            if let Some(arr) = self.feat_spec.take() {
+                pool.recycle(arr.into_raw_vec());
+            }
+        }
+    }
+}
 impl<T> fmt::Debug for DsBatch<T>
 where
     T: Data,
@@ -561,33 +1125,158 @@ where
     }
 }
 
-fn unpack_pad<Ts, To, F>(mut f: F, samples: &mut [Sample<Ts>], len: usize) -> Result<ArrayD<To>>
+/// Streaming per-sample statistics (SNR, applied gain, loudness, clip length, ...)
+/// accumulated batch by batch over a large corpus, queryable for arbitrary quantiles in
+/// bounded memory via a [`TDigest`] per metric. This avoids materializing every
+/// sample's properties just to print e.g. p01/p50/p99 SNR or loudness over tens of
+/// millions of samples.
+///
+/// Does not track active-speech ratio: that needs a voice-activity detector, and this
+/// dataset pipeline does not have one to call into here.
+pub struct DatasetStats {
+    snr: TDigest,
+    gain: TDigest,
+    loudness: TDigest,
+    length: TDigest,
+    n_samples: usize,
+}
+
+impl DatasetStats {
+    pub fn new() -> Self {
+        let compression = 100.0;
+        DatasetStats {
+            snr: TDigest::new(compression),
+            gain: TDigest::new(compression),
+            loudness: TDigest::new(compression),
+            length: TDigest::new(compression),
+            n_samples: 0,
+        }
+    }
+
+    /// RMS magnitude of each sample's speech spectrogram, one value per batch member;
+    /// a frequency-domain stand-in for waveform RMS/loudness (Parseval: mean squared
+    /// magnitude in the FFT domain equals mean squared amplitude in the time domain).
+    /// Only each sample's first `lengths[i]` frames count, not the collate padding.
+    fn per_sample_loudness(speech: &ArrayD<Complex32>, lengths: &Array1<usize>) -> Vec<f64> {
+        (0..speech.len_of(Axis(0)))
+            .map(|i| {
+                let sample = speech.index_axis(Axis(0), i);
+                let sample = sample.slice_axis(Axis(1), ndarray::Slice::from(..lengths[i]));
+                let sum_sq: f64 = sample.iter().map(|c| (c.norm() as f64).powi(2)).sum();
+                (sum_sq / sample.len().max(1) as f64).sqrt()
+            })
+            .collect()
+    }
+
+    /// Feed a batch's per-sample SNR, gain, loudness and frame length into the
+    /// accumulators.
+    pub fn update(&mut self, batch: &DsBatch<Complex32>) {
+        for &snr in &batch.snr {
+            self.snr.push(snr as f64);
+        }
+        for &gain in &batch.gain {
+            self.gain.push(gain as f64);
+        }
+        for loudness in Self::per_sample_loudness(&batch.speech, &batch.lengths) {
+            self.loudness.push(loudness);
+        }
+        for &len in batch.lengths.iter() {
+            self.length.push(len as f64);
+        }
+        self.n_samples += batch.batch_size();
+    }
+
+    pub fn snr_quantile(&mut self, q: f64) -> f64 {
+        self.snr.quantile(q)
+    }
+    pub fn gain_quantile(&mut self, q: f64) -> f64 {
+        self.gain.quantile(q)
+    }
+    pub fn loudness_quantile(&mut self, q: f64) -> f64 {
+        self.loudness.quantile(q)
+    }
+    pub fn length_quantile(&mut self, q: f64) -> f64 {
+        self.length.quantile(q)
+    }
+    pub fn n_samples(&self) -> usize {
+        self.n_samples
+    }
+}
+
+impl Default for DatasetStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Assemble a batch array of `samples.len()` items, each brought to `len` along
+/// `Axis(1)` per `mode`, writing directly into one pre-allocated (optionally pooled)
+/// buffer instead of padding every sample individually and then `ndarray::stack`-ing
+/// the result. `len` is expected to already be the batch's target length (the longest
+/// sample of the batch's span, or the shortest sample's length for
+/// [`CollateMode::Truncate`]).
+fn unpack_pad<Ts, To, F>(
+    mut f: F,
+    samples: &mut [Sample<Ts>],
+    len: usize,
+    mode: CollateMode,
+    pool: Option<&Arc<BufferPool<To>>>,
+) -> Result<ArrayD<To>>
 where
     Ts: Data,
-    To: Data,
+    To: Data + Zero,
     F: FnMut(&mut Sample<Ts>) -> &mut ArrayD<To>,
 {
-    let mut out: Vec<ArrayViewMutD<To>> = Vec::with_capacity(samples.len());
-    for sample in samples.iter_mut() {
-        let x: &mut ArrayD<To> = f(sample);
+    if samples.is_empty() {
+        return Ok(ArrayD::<To>::zeros(IxDyn(&[0])));
+    }
+    let mut out_shape: Vec<usize> = f(&mut samples[0]).shape().into();
+    out_shape[1] = len;
+    out_shape.insert(0, samples.len());
+    let total: usize = out_shape.iter().product();
 
-        let missing = len.saturating_sub(x.len_of(Axis(1)));
-        if missing > 0 {
-            let mut shape: Vec<usize> = x.shape().into();
-            shape[1] = missing;
-            let tmp: ArrayD<To> = ArrayD::<To>::zeros(shape);
-            x.append(Axis(1), tmp.into_dimensionality()?.view())?;
+    let flat = match pool {
+        Some(p) => p.checkout(total),
+        None => vec![To::zero(); total],
+    };
+    let mut out = Array::from_shape_vec(IxDyn(&out_shape), flat)?;
+    for (i, sample) in samples.iter_mut().enumerate() {
+        let x: &mut ArrayD<To> = f(sample);
+        let src_len = x.len_of(Axis(1));
+        let copy_len = src_len.min(len);
+        let mut dest = out.index_axis_mut(Axis(0), i);
+        dest.slice_axis_mut(Axis(1), ndarray::Slice::from(..copy_len))
+            .assign(&x.slice_axis(Axis(1), ndarray::Slice::from(..copy_len)));
+        if copy_len >= len || src_len == 0 {
+            continue;
         }
-        out.push(x.view_mut());
-    }
-    let out: Vec<ArrayViewD<To>> = out.iter().map(|s| s.view()).collect();
-    if !out.windows(2).all(|w| w[0].shape() == w[1].shape()) {
-        eprintln!("Shapes do not match!");
-        for outs in out.iter() {
-            eprintln!("  shape: {:?}", outs.shape());
+        match mode {
+            // The pool/fresh-alloc buffer is already zero-filled; nothing left to do.
+            CollateMode::ZeroPad | CollateMode::Truncate => {}
+            // Tile the sample from its start to fill the remaining tail.
+            CollateMode::RepeatPad => {
+                for pos in copy_len..len {
+                    let src_idx = pos % src_len;
+                    let frame = x.index_axis(Axis(1), src_idx);
+                    dest.index_axis_mut(Axis(1), pos).assign(&frame);
+                }
+            }
+            // Mirror the existing frames back from the end (no edge repeat), the same
+            // convention as e.g. numpy's `reflect` padding mode.
+            CollateMode::ReflectPad if src_len > 1 => {
+                let period = 2 * (src_len - 1);
+                for pos in copy_len..len {
+                    let m = pos % period;
+                    let src_idx = if m < src_len { m } else { period - m };
+                    let frame = x.index_axis(Axis(1), src_idx);
+                    dest.index_axis_mut(Axis(1), pos).assign(&frame);
+                }
+            }
+            // A single-frame sample has nothing to mirror; fall back to zero padding.
+            CollateMode::ReflectPad => {}
         }
     }
-    Ok(ndarray::stack(Axis(0), out.as_slice())?.into_dyn())
+    Ok(out)
 }
 
 #[cfg(test)]
@@ -676,4 +1365,87 @@ mod tests {
         }
         Ok(())
     }
+
+    /// Covers the behaviors added on top of the plain fixed-stride `get_batch` loop
+    /// above: length-bucketed batching, the `RepeatPad`/`ReflectPad` collate modes, epoch
+    /// dedup, importance-sampled draws, and consuming `batch_stream` directly.
+    #[test]
+    pub fn test_dataloader_options() -> Result<()> {
+        seed_from_u64(42);
+        let fft_size = 960;
+        let hop_size = Some(480);
+        let nb_erb = Some(32);
+        let nb_spec = None;
+        let norm_alpha = None;
+        let sr = 48000;
+        let ds_dir = "../assets/";
+        let cfg = DatasetConfigJson::open("../assets/dataset.cfg")?;
+        let split = Split::Train;
+        let builder = DatasetBuilder::new(ds_dir, sr)
+            .df_params(fft_size, hop_size, nb_erb, nb_spec, norm_alpha)
+            .max_len(1.);
+        let make_ds = || -> Result<Datasets> {
+            Ok(Datasets {
+                train: builder
+                    .clone()
+                    .dataset(cfg.split_config(Split::Train))
+                    .build_fft_dataset()?,
+                valid: builder
+                    .clone()
+                    .dataset(cfg.split_config(Split::Valid))
+                    .build_fft_dataset()?,
+                test: builder
+                    .clone()
+                    .dataset(cfg.split_config(Split::Valid))
+                    .build_fft_dataset()?,
+            })
+        };
+
+        for collate_mode in [
+            CollateMode::ZeroPad,
+            CollateMode::RepeatPad,
+            CollateMode::ReflectPad,
+        ] {
+            let mut loader = DataLoader::builder(make_ds()?)
+                .num_threads(1)
+                .batch_size(2)
+                .batch_size_eval(1)
+                .bucket_by_length(2)
+                .collate_mode(collate_mode)
+                .dedup_fp_rate(0.01)
+                .build()?;
+            loader.start_epoch(split, 0)?;
+            let mut n_samples = 0;
+            while let Some(batch) = loader.get_batch::<Complex32>()? {
+                n_samples += batch.batch_size();
+            }
+            assert!(n_samples > 0);
+            // Every dedup'd epoch's fill ratio must be well-formed, whether or not any
+            // duplicates were actually seen.
+            assert!(loader.dedup_fill_ratio().unwrap() >= 0.0);
+        }
+
+        // Importance sampling draws with replacement, so `batch_stream` must still
+        // reassemble every drawn id in order without hanging or duplicating state
+        // across epochs (the chunk0-1 double-call regression this test also guards).
+        let mut loader = DataLoader::builder(make_ds()?)
+            .num_threads(1)
+            .batch_size(2)
+            .batch_size_eval(1)
+            .importance_sampling(0.9, 0.5)
+            .build()?;
+        for epoch in 0..2 {
+            loader.start_epoch(split, epoch)?;
+            let mut n_samples = 0;
+            {
+                let mut stream = loader.batch_stream::<Complex32>();
+                while let Some(batch) = block_on(stream.next()).transpose()? {
+                    n_samples += batch.batch_size();
+                }
+            }
+            assert!(n_samples > 0);
+            loader.push_loss(0, 1.0);
+        }
+        Ok(())
+    }
 }
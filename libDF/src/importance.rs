@@ -0,0 +1,255 @@
+//! Loss-aware (hard-example / importance) sampling: the training loop feeds back
+//! per-sample losses keyed by sample id via [`ImportanceSampler::push_loss`], and the
+//! loader biases future draws towards samples with high running loss via
+//! [`ImportanceSampler::sample`]. Priority is an exponentially-decayed running loss
+//! (decay factor `gamma`), so old losses fade out instead of permanently pinning a
+//! sample as "hard". Draws blend uniform and priority-weighted sampling via `alpha`
+//! (`0.0` = pure uniform/unbiased coverage, `1.0` = pure hard-example mining), and
+//! periodically re-inject ids that have never received a loss so cold samples are not
+//! starved out of training entirely.
+//!
+//! Backed by a bounded 4-ary max-heap (cache-friendlier sift than a binary heap,
+//! since 4 children fit a cache line's worth of `f64` priorities) keyed on priority,
+//! with a hash map from sample id to heap index so [`ImportanceSampler::push_loss`]
+//! updates are `O(log n)` instead of a linear scan.
+
+use std::collections::HashMap;
+
+use crate::rng::DfRng;
+
+const ARITY: usize = 4;
+
+/// On every `COLD_INJECT_PERIOD`-th call to [`ImportanceSampler::sample`], every
+/// `COLD_INJECT_PERIOD`-th draw of that call is forced to a cold, never-scored id (while
+/// any remain), whatever priority or uniform sampling would have picked.
+const COLD_INJECT_PERIOD: usize = 4;
+
+struct Entry {
+    id: usize,
+    priority: f64,
+}
+
+/// A bounded max-heap over per-sample priorities, keyed by sample id.
+pub struct ImportanceSampler {
+    heap: Vec<Entry>,
+    index: HashMap<usize, usize>,
+    /// Exponential decay applied to a sample's stored priority before each update.
+    gamma: f64,
+    /// Blend factor: fraction of each `sample()` draw taken by priority rather than
+    /// uniformly at random.
+    alpha: f64,
+    /// Maximum number of distinct ids tracked; the lowest-priority entry is evicted
+    /// to make room for a new id once full.
+    capacity: usize,
+    calls: usize,
+}
+
+impl ImportanceSampler {
+    pub fn new(capacity: usize, gamma: f64, alpha: f64) -> Self {
+        ImportanceSampler {
+            heap: Vec::with_capacity(capacity.min(1 << 16)),
+            index: HashMap::new(),
+            gamma: gamma.clamp(0.0, 1.0),
+            alpha: alpha.clamp(0.0, 1.0),
+            capacity: capacity.max(1),
+            calls: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    #[inline]
+    fn parent(i: usize) -> usize {
+        (i - 1) / ARITY
+    }
+
+    #[inline]
+    fn first_child(i: usize) -> usize {
+        i * ARITY + 1
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.index.insert(self.heap[i].id, i);
+        self.index.insert(self.heap[j].id, j);
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 {
+            let p = Self::parent(i);
+            if self.heap[p].priority >= self.heap[i].priority {
+                break;
+            }
+            self.swap(p, i);
+            i = p;
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let first = Self::first_child(i);
+            if first >= self.heap.len() {
+                break;
+            }
+            let last = (first + ARITY).min(self.heap.len());
+            let mut largest = i;
+            for c in first..last {
+                if self.heap[c].priority > self.heap[largest].priority {
+                    largest = c;
+                }
+            }
+            if largest == i {
+                break;
+            }
+            self.swap(i, largest);
+            i = largest;
+        }
+    }
+
+    /// Decay the stored priority for `id` by `gamma` and add `loss`, inserting a new
+    /// entry if `id` has not been scored before. Evicts the current lowest-priority
+    /// entry if this would grow the heap past `capacity`.
+    pub fn push_loss(&mut self, id: usize, loss: f64) {
+        if let Some(&pos) = self.index.get(&id) {
+            self.heap[pos].priority = self.heap[pos].priority * self.gamma + loss;
+            // The new priority can move in either direction relative to its
+            // neighbours depending on gamma/loss, so try both directions.
+            self.sift_up(pos);
+            self.sift_down(pos);
+            return;
+        }
+        if self.heap.len() >= self.capacity {
+            self.evict_min();
+        }
+        self.heap.push(Entry { id, priority: loss });
+        let pos = self.heap.len() - 1;
+        self.index.insert(id, pos);
+        self.sift_up(pos);
+    }
+
+    /// Drop the globally lowest-priority entry (a linear scan; `capacity` evictions
+    /// are expected to be rare relative to `push_loss` calls).
+    fn evict_min(&mut self) {
+        let Some((min_pos, _)) = self
+            .heap
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.priority.partial_cmp(&b.1.priority).unwrap())
+        else {
+            return;
+        };
+        let last = self.heap.len() - 1;
+        self.index.remove(&self.heap[min_pos].id);
+        if min_pos != last {
+            self.heap.swap(min_pos, last);
+            self.index.insert(self.heap[min_pos].id, min_pos);
+        }
+        self.heap.pop();
+        if min_pos < self.heap.len() {
+            self.sift_down(min_pos);
+            self.sift_up(min_pos);
+        }
+    }
+
+    /// Draw `k` sample ids for the next epoch, blending priority-weighted draws from
+    /// the heap (`alpha` fraction) with uniform draws over `0..n_ids` (the rest), and
+    /// periodically forcing in never-scored cold ids (see [`COLD_INJECT_PERIOD`]) so they
+    /// are not starved out of training. Draws are with replacement: a very hard example can legitimately be
+    /// picked more than once per epoch.
+    pub fn sample(&mut self, k: usize, n_ids: usize, rng: &mut DfRng) -> Vec<usize> {
+        self.calls += 1;
+        let mut out = Vec::with_capacity(k);
+        if n_ids == 0 {
+            return out;
+        }
+        // Build the cumulative-weight prefix sum once per call rather than rescanning
+        // the heap for every draw: a fresh O(n) linear scan per draw makes the whole
+        // call O(n * k), which defeats the point of a heap-backed sampler once `k` and
+        // `n` are both in the millions. Priorities are fixed for the duration of this
+        // call (`push_loss` only runs between epochs), so one prefix sum supports all
+        // `k` draws via O(log n) binary search each, for O(n + k log n) overall.
+        let mut prefix = Vec::with_capacity(self.heap.len());
+        let mut total_priority = 0.0;
+        for entry in &self.heap {
+            total_priority += entry.priority.max(0.0);
+            prefix.push(total_priority);
+        }
+        for i in 0..k {
+            let inject_cold = self.calls % COLD_INJECT_PERIOD == 0
+                && i % COLD_INJECT_PERIOD == 0
+                && self.index.len() < n_ids;
+            let use_priority = !inject_cold && !self.heap.is_empty() && total_priority > 0.0
+                && rng.next_f32_unit() < self.alpha as f32;
+            let id = if inject_cold {
+                self.random_cold_id(n_ids, rng)
+            } else if use_priority {
+                self.weighted_pick(&prefix, total_priority, rng)
+            } else {
+                (rng.next_u64() % n_ids as u64) as usize
+            };
+            out.push(id);
+        }
+        out
+    }
+
+    /// Binary search `prefix` (the cumulative-weight array built once in [`Self::sample`])
+    /// for the heap entry whose weight interval contains a uniformly drawn target,
+    /// giving an O(log n) weighted pick instead of a linear scan over the heap.
+    fn weighted_pick(&self, prefix: &[f64], total_priority: f64, rng: &mut DfRng) -> usize {
+        let target = rng.uniform_range(0.0, 1.0) as f64 * total_priority;
+        let idx = prefix.partition_point(|&cum| cum <= target);
+        match self.heap.get(idx) {
+            Some(entry) => entry.id,
+            None => self.heap.last().map(|e| e.id).unwrap_or(0),
+        }
+    }
+
+    /// Pick an id in `0..n_ids` that has never received a `push_loss` call, falling
+    /// back to a plain uniform draw if every id has already been scored.
+    fn random_cold_id(&self, n_ids: usize, rng: &mut DfRng) -> usize {
+        if self.index.len() >= n_ids {
+            return (rng.next_u64() % n_ids as u64) as usize;
+        }
+        loop {
+            let candidate = (rng.next_u64() % n_ids as u64) as usize;
+            if !self.index.contains_key(&candidate) {
+                return candidate;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_biases_towards_high_loss_ids_under_pure_priority() {
+        let mut sampler = ImportanceSampler::new(4, 1.0, 1.0);
+        sampler.push_loss(0, 100.0);
+        sampler.push_loss(1, 0.01);
+        sampler.push_loss(2, 0.01);
+        sampler.push_loss(3, 0.01);
+        let mut rng = DfRng::new(42);
+        let draws = sampler.sample(1_000, 4, &mut rng);
+        let hard_fraction = draws.iter().filter(|&&id| id == 0).count() as f64 / draws.len() as f64;
+        assert!(hard_fraction > 0.9, "hard_fraction={}", hard_fraction);
+    }
+
+    #[test]
+    fn push_loss_evicts_lowest_priority_entry_once_at_capacity() {
+        let mut sampler = ImportanceSampler::new(2, 1.0, 1.0);
+        sampler.push_loss(0, 1.0);
+        sampler.push_loss(1, 2.0);
+        assert_eq!(sampler.len(), 2);
+        sampler.push_loss(2, 3.0);
+        assert_eq!(sampler.len(), 2);
+        assert!(!sampler.index.contains_key(&0));
+    }
+}
@@ -0,0 +1,155 @@
+//! A t-digest: an approximate, mergeable quantile sketch in bounded memory.
+//!
+//! Values are buffered and periodically compressed into weighted centroids, with
+//! centroid size bounded by the scale function `k(q) = (delta / 2pi) * asin(2q - 1)` so
+//! centroids near the tails stay small (precise tails) while centroids near the median
+//! may hold many more points (coarse middle). See Dunning & Ertl, "Computing Extremely
+//! Accurate Quantiles Using t-Digests".
+
+#[derive(Clone, Copy, Debug)]
+struct Centroid {
+    mean: f64,
+    weight: f64,
+}
+
+pub struct TDigest {
+    /// Compression factor `delta`; higher means more centroids and better accuracy.
+    compression: f64,
+    centroids: Vec<Centroid>,
+    buffer: Vec<f64>,
+    buffer_cap: usize,
+    count: f64,
+    min: f64,
+    max: f64,
+}
+
+impl TDigest {
+    pub fn new(compression: f64) -> Self {
+        TDigest {
+            compression: compression.max(1.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            buffer_cap: 1024,
+            count: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn push(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(value);
+        if self.buffer.len() >= self.buffer_cap {
+            self.compress();
+        }
+    }
+
+    pub fn count(&self) -> f64 {
+        self.count + self.buffer.len() as f64
+    }
+
+    fn k(&self, q: f64) -> f64 {
+        (self.compression / (2.0 * std::f64::consts::PI)) * (2.0 * q - 1.0).clamp(-1.0, 1.0).asin()
+    }
+
+    /// Merge the buffer into the centroid list, re-clustering so each centroid's
+    /// quantile span maps to at most one unit of the `k` scale.
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut points: Vec<Centroid> = self
+            .centroids
+            .drain(..)
+            .chain(self.buffer.drain(..).map(|v| Centroid { mean: v, weight: 1.0 }))
+            .collect();
+        points.sort_by(|a, b| a.mean.partial_cmp(&b.mean).unwrap());
+
+        let total: f64 = points.iter().map(|c| c.weight).sum();
+        if total <= 0.0 {
+            return;
+        }
+
+        let mut merged = Vec::with_capacity(points.len());
+        let mut iter = points.into_iter();
+        let mut cur = iter.next().unwrap();
+        let mut weight_before = 0.0;
+        let mut q0 = 0.0;
+        for p in iter {
+            let q = (weight_before + cur.weight + p.weight) / total;
+            if self.k(q) - self.k(q0) <= 1.0 {
+                let weight = cur.weight + p.weight;
+                cur = Centroid {
+                    mean: (cur.mean * cur.weight + p.mean * p.weight) / weight,
+                    weight,
+                };
+            } else {
+                weight_before += cur.weight;
+                q0 = weight_before / total;
+                merged.push(cur);
+                cur = p;
+            }
+        }
+        merged.push(cur);
+        self.centroids = merged;
+        self.count = total;
+    }
+
+    /// Estimate the value at quantile `q` (0..=1), interpolating linearly between the
+    /// two centroids straddling the target cumulative weight and clamping to the
+    /// observed min/max at the tails.
+    pub fn quantile(&mut self, q: f64) -> f64 {
+        self.compress();
+        if self.centroids.is_empty() || self.count <= 0.0 {
+            return f64::NAN;
+        }
+        if self.centroids.len() == 1 {
+            return self.centroids[0].mean;
+        }
+        let q = q.clamp(0.0, 1.0);
+        let target = q * self.count;
+
+        let mut mids = Vec::with_capacity(self.centroids.len());
+        let mut cum = 0.0;
+        for c in &self.centroids {
+            mids.push(cum + c.weight / 2.0);
+            cum += c.weight;
+        }
+        if target <= mids[0] {
+            return self.min;
+        }
+        if target >= *mids.last().unwrap() {
+            return self.max;
+        }
+        for i in 0..mids.len() - 1 {
+            let (m0, m1) = (mids[i], mids[i + 1]);
+            if target <= m1 {
+                let (c0, c1) = (self.centroids[i], self.centroids[i + 1]);
+                let frac = if m1 > m0 { (target - m0) / (m1 - m0) } else { 0.0 };
+                return c0.mean + frac * (c1.mean - c0.mean);
+            }
+        }
+        self.max
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quantiles_of_uniform_samples_are_approximately_correct() {
+        let mut td = TDigest::new(100.0);
+        for i in 0..10_000 {
+            td.push(i as f64);
+        }
+        let p50 = td.quantile(0.5);
+        let p99 = td.quantile(0.99);
+        assert!((p50 - 4999.5).abs() < 50.0, "p50={}", p50);
+        assert!((p99 - 9899.0).abs() < 100.0, "p99={}", p99);
+    }
+}